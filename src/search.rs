@@ -1,3 +1,4 @@
+use regex::RegexBuilder;
 use semver::{Version, VersionReq};
 use std::collections::BTreeMap;
 use std::ops::RangeInclusive;
@@ -81,6 +82,24 @@ pub trait Search {
         options: SearchOptions,
     ) -> anyhow::Result<Matches>;
 
+    /// Report whether any invoice matches the given term and filter.
+    ///
+    /// The default implementation derives this from `query`, but implementations
+    /// are encouraged to override it with a path that short-circuits on the first
+    /// match instead of cloning every matching invoice.
+    fn exists(&self, term: String, filter: String) -> anyhow::Result<bool> {
+        Ok(self.query(term, filter, SearchOptions::default())?.total > 0)
+    }
+
+    /// Count how many invoices match the given term and filter.
+    ///
+    /// The default implementation derives this from `query`, but implementations
+    /// are encouraged to override it with a path that counts matches without
+    /// cloning any of them.
+    fn count(&self, term: String, filter: String) -> anyhow::Result<u64> {
+        Ok(self.query(term, filter, SearchOptions::default())?.total)
+    }
+
     /// Given an invoice, extract information from it that will be useful for searching.
     ///
     /// This high-level feature does not provide any guarantees about how it will
@@ -96,12 +115,172 @@ pub trait Search {
     fn index(&mut self, document: &crate::Invoice) -> anyhow::Result<()>;
 }
 
+/// The lifecycle state of an invoice held in an index.
+///
+/// Modeled on cargo's `IndexSummary`, which distinguishes a `Candidate` from a
+/// `Yanked` entry: keeping the variant alongside the invoice lets a query decide
+/// whether to surface a yanked invoice without having to consult the invoice's
+/// `yanked` field separately (and risk the two falling out of sync).
+#[derive(Clone)]
+enum Indexed {
+    /// An invoice that has not been yanked.
+    Active(crate::Invoice),
+    /// An invoice that has been yanked, and should only be returned when
+    /// explicitly asked for.
+    Yanked(crate::Invoice),
+}
+
+impl Indexed {
+    /// Construct the `Indexed` variant appropriate for the invoice's `yanked` flag.
+    fn new(invoice: crate::Invoice) -> Self {
+        if invoice.yanked.unwrap_or(false) {
+            Indexed::Yanked(invoice)
+        } else {
+            Indexed::Active(invoice)
+        }
+    }
+
+    /// Whether this entry is yanked.
+    fn is_yanked(&self) -> bool {
+        matches!(self, Indexed::Yanked(_))
+    }
+
+    /// Borrow the underlying invoice, regardless of lifecycle state.
+    fn as_invoice(&self) -> &crate::Invoice {
+        match self {
+            Indexed::Active(invoice) => invoice,
+            Indexed::Yanked(invoice) => invoice,
+        }
+    }
+}
+
+/// Apply a single `field: value` clause to a `QueryFilter` being built up by `QueryFilter::parse`.
+fn apply_filter_clause(parsed: &mut QueryFilter, field: &str, value: &str) -> anyhow::Result<()> {
+    match field {
+        "version" => {
+            let req = VersionReq::parse(value)
+                .map_err(|e| anyhow::anyhow!("invalid version filter '{}': {}", value, e))?;
+            parsed.version = Some(req);
+        }
+        "media_type" => parsed.media_type = Some(value.to_owned()),
+        "author" => parsed.author = Some(value.to_owned()),
+        other => return Err(anyhow::anyhow!("unknown filter field '{}'", other)),
+    }
+    Ok(())
+}
+
+/// A structured, parsed query filter.
+///
+/// `filter` strings use a small `field: value` grammar, with clauses separated by
+/// commas -- e.g. `"version: ^1.2, media_type: text/toml, author: butcher"`. Every
+/// clause present is applied conjunctively against an invoice.
+#[derive(Debug, Default, Clone)]
+pub struct QueryFilter {
+    /// Only invoices whose version satisfies this requirement match.
+    pub version: Option<VersionReq>,
+    /// Only invoices with a parcel of this media type match.
+    pub media_type: Option<String>,
+    /// Only invoices with an author containing this text match.
+    pub author: Option<String>,
+}
+
+impl QueryFilter {
+    /// Parse a filter string into its structured form.
+    ///
+    /// An empty string parses to a filter with no constraints, which matches
+    /// everything. A malformed clause or an unparseable `VersionReq` is reported
+    /// as a descriptive error, rather than silently matching nothing.
+    ///
+    /// A clause's value may itself contain commas -- most notably a `version`
+    /// clause using the comma-joined predicate list `VersionReq` already
+    /// supports (e.g. `"version: >= 1.2.0, < 2.0.0"`) -- so a token that has no
+    /// `field:` prefix of its own is treated as a continuation of the previous
+    /// clause's value rather than a clause in its own right.
+    pub fn parse(filter: &str) -> anyhow::Result<Self> {
+        let mut parsed = QueryFilter::default();
+        let mut current: Option<(&str, String)> = None;
+
+        for token in filter.split(',') {
+            let token = token.trim();
+            if token.is_empty() {
+                continue;
+            }
+
+            match token.split_once(':') {
+                Some((field, value)) => {
+                    if let Some((field, value)) = current.take() {
+                        apply_filter_clause(&mut parsed, field, &value)?;
+                    }
+                    current = Some((field.trim(), value.trim().to_owned()));
+                }
+                None => match &mut current {
+                    Some((_, value)) => {
+                        value.push_str(", ");
+                        value.push_str(token);
+                    }
+                    None => {
+                        return Err(anyhow::anyhow!(
+                            "invalid filter clause '{}': expected 'field: value'",
+                            token
+                        ));
+                    }
+                },
+            }
+        }
+
+        if let Some((field, value)) = current {
+            apply_filter_clause(&mut parsed, field, &value)?;
+        }
+
+        Ok(parsed)
+    }
+
+    /// Check whether the given invoice satisfies every constraint present in this filter.
+    fn matches(&self, invoice: &crate::Invoice) -> bool {
+        if let Some(req) = &self.version {
+            match Version::parse(invoice.bindle.version.as_str()) {
+                Ok(ver) if req.matches(&ver) => {}
+                _ => return false,
+            }
+        }
+
+        if let Some(media_type) = &self.media_type {
+            let has_parcel = invoice
+                .parcels
+                .as_ref()
+                .map(|parcels| parcels.iter().any(|p| &p.label.media_type == media_type))
+                .unwrap_or(false);
+            if !has_parcel {
+                return false;
+            }
+        }
+
+        if let Some(author) = &self.author {
+            let has_author = invoice
+                .bindle
+                .authors
+                .as_ref()
+                .map(|authors| authors.iter().any(|a| a.contains(author.as_str())))
+                .unwrap_or(false);
+            if !has_author {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
 /// Implements strict query processing.
 pub struct StrictEngine {
     // A BTreeMap will keep the records in a predictable order, which makes the
     // search results predictable. This greatly simplifies the process of doing offsets
     // and limits.
-    index: BTreeMap<String, crate::Invoice>,
+    //
+    // The inner map is keyed by version so that every version of a given bindle name
+    // can be indexed simultaneously, and is itself a BTreeMap so that versions come
+    // back out in a predictable (ascending) order.
+    index: BTreeMap<String, BTreeMap<Version, Indexed>>,
 }
 
 impl Default for StrictEngine {
@@ -119,39 +298,64 @@ impl Search for StrictEngine {
         filter: String,
         options: SearchOptions,
     ) -> anyhow::Result<Matches> {
+        let query_filter = QueryFilter::parse(&filter)?;
+
         let mut found: Vec<crate::Invoice> = self
             .index
-            .iter()
-            .filter(|(key, value)| {
-                // Term and version have to be exact matches.
-                // TODO: Version should have matching turned on.
-                *key == &term && version_compare(value.bindle.version.as_str(), &filter)
+            .get(&term)
+            .map(|versions| {
+                // Term has to be an exact match. Every version whose invoice
+                // satisfies the filter is a match, newest first, and yanked
+                // invoices are only included when explicitly requested.
+                versions
+                    .iter()
+                    .rev()
+                    .filter(|(_, indexed)| {
+                        query_filter.matches(indexed.as_invoice())
+                            && (options.yanked || !indexed.is_yanked())
+                    })
+                    .map(|(_, indexed)| indexed.as_invoice().clone())
+                    .collect()
             })
-            .map(|(_, v)| (*v).clone())
-            .collect();
+            .unwrap_or_default();
 
         let mut matches = Matches::new(&options);
         matches.strict = true;
-        matches.yanked = false;
-        matches.total = found.len() as u64;
+        matches.yanked = options.yanked;
 
-        if matches.offset >= matches.total {
-            // We're past the end of the search results. Return an empty matches object.
-            matches.more = false;
-            return Ok(matches);
-        }
-
-        // Apply offset and limit
-        let mut last_index = matches.offset + matches.limit as u64 - 1;
-        if last_index >= matches.total {
-            last_index = matches.total - 1;
-        }
+        Ok(paginate(matches, &mut found))
+    }
 
-        matches.more = matches.total > last_index + 1;
-        let range = RangeInclusive::new(matches.offset as usize, last_index as usize);
-        matches.invoices = found.drain(range).collect();
+    // Skips the per-invoice clone and the offset/limit drain that `query` does,
+    // short-circuiting on the first matching version instead of collecting them all.
+    fn exists(&self, term: String, filter: String) -> anyhow::Result<bool> {
+        let query_filter = QueryFilter::parse(&filter)?;
+        Ok(self
+            .index
+            .get(&term)
+            .map(|versions| {
+                versions.iter().any(|(_, indexed)| {
+                    !indexed.is_yanked() && query_filter.matches(indexed.as_invoice())
+                })
+            })
+            .unwrap_or(false))
+    }
 
-        Ok(matches)
+    // As with `exists`, avoids cloning any invoice -- it only needs to count versions.
+    fn count(&self, term: String, filter: String) -> anyhow::Result<u64> {
+        let query_filter = QueryFilter::parse(&filter)?;
+        Ok(self
+            .index
+            .get(&term)
+            .map(|versions| {
+                versions
+                    .iter()
+                    .filter(|(_, indexed)| {
+                        !indexed.is_yanked() && query_filter.matches(indexed.as_invoice())
+                    })
+                    .count() as u64
+            })
+            .unwrap_or(0))
     }
 
     /// Given an invoice, extract information from it that will be useful for searching.
@@ -167,41 +371,199 @@ impl Search for StrictEngine {
     /// as such, following the protocol specification's requirements for yanked
     /// invoices.
     fn index(&mut self, invoice: &crate::Invoice) -> anyhow::Result<()> {
+        let version = Version::parse(invoice.bindle.version.as_str())?;
         self.index
-            .insert(invoice.bindle.name.clone(), (*invoice).clone());
+            .entry(invoice.bindle.name.clone())
+            .or_default()
+            .insert(version, Indexed::new((*invoice).clone()));
         Ok(())
     }
 }
 
-/// Check whether the given version is within the legal range.
-///
-/// An empty range matches anything.
-///
-/// A range that fails to parse matches nothing.
+/// Apply the offset and limit from `matches` to `found`, filling in `total`, `more`,
+/// and `invoices` along the way.
 ///
-/// An empty version matches nothing (unless the requirement is empty)
-///
-/// A version that fails to parse matches nothing (unless the requirement is empty).
+/// This is shared by every `Search` implementation so that offset/limit semantics
+/// stay identical regardless of how the candidate set was produced.
+fn paginate(mut matches: Matches, found: &mut Vec<crate::Invoice>) -> Matches {
+    matches.total = found.len() as u64;
+
+    if matches.offset >= matches.total {
+        // We're past the end of the search results. Return an empty matches object.
+        matches.more = false;
+        return matches;
+    }
+
+    // Apply offset and limit
+    let mut last_index = matches.offset + matches.limit as u64 - 1;
+    if last_index >= matches.total {
+        last_index = matches.total - 1;
+    }
+
+    matches.more = matches.total > last_index + 1;
+    let range = RangeInclusive::new(matches.offset as usize, last_index as usize);
+    matches.invoices = found.drain(range).collect();
+
+    matches
+}
+
+/// An invoice together with the text document extracted from it for full-text search.
+struct IndexedDocument {
+    indexed: Indexed,
+    document: String,
+}
+
+/// Extract a searchable text document from an invoice.
 ///
-/// In all other cases, if the version satisfies the requirement, this returns true.
-/// And if it fails to satisfy the requirement, this returns false.
-pub fn version_compare(version: &str, requirement: &str) -> bool {
-    if requirement.is_empty() {
-        return true;
-    }
-
-    if let Ok(req) = VersionReq::parse(requirement) {
-        println!("Parsed {}", req);
-        return match Version::parse(version) {
-            Ok(ver) => req.matches(&ver),
-            Err(e) => {
-                eprintln!("Match failed with an error: {}", e);
-                false
+/// This pulls together the name, description, authors, each parcel's label name and
+/// media type, and any annotation keys/values, so that a `StandardEngine` query can
+/// match against any of them.
+fn search_document(invoice: &crate::Invoice) -> String {
+    let mut doc = String::new();
+
+    doc.push_str(&invoice.bindle.name);
+    doc.push(' ');
+
+    if let Some(description) = &invoice.bindle.description {
+        doc.push_str(description);
+        doc.push(' ');
+    }
+
+    if let Some(authors) = &invoice.bindle.authors {
+        doc.push_str(&authors.join(" "));
+        doc.push(' ');
+    }
+
+    if let Some(parcels) = &invoice.parcels {
+        for parcel in parcels {
+            doc.push_str(&parcel.label.name);
+            doc.push(' ');
+            doc.push_str(&parcel.label.media_type);
+            doc.push(' ');
+
+            if let Some(annotations) = &parcel.label.annotations {
+                for (key, value) in annotations.iter() {
+                    doc.push_str(key);
+                    doc.push(' ');
+                    doc.push_str(value);
+                    doc.push(' ');
+                }
             }
+        }
+    }
+
+    if let Some(annotations) = &invoice.annotations {
+        for (key, value) in annotations.iter() {
+            doc.push_str(key);
+            doc.push(' ');
+            doc.push_str(value);
+            doc.push(' ');
+        }
+    }
+
+    doc
+}
+
+/// Implements standard query processing.
+///
+/// Unlike `StrictEngine`, a `StandardEngine` indexes the full text of an invoice --
+/// not just its name -- so that a query term can match a substring or regular
+/// expression against the name, description, authors, parcel labels, or
+/// annotations. When `SearchOptions.strict` is set, it falls back to the same
+/// exact-name-match behavior as `StrictEngine`, so a single engine can serve both
+/// modes described in the protocol specification.
+pub struct StandardEngine {
+    // Keyed the same way as StrictEngine's index (name, then version) so that
+    // strict-mode lookups on this engine behave identically.
+    index: BTreeMap<String, BTreeMap<Version, IndexedDocument>>,
+}
+
+impl Default for StandardEngine {
+    fn default() -> Self {
+        StandardEngine {
+            index: BTreeMap::new(),
+        }
+    }
+}
+
+impl Search for StandardEngine {
+    fn query(
+        &self,
+        term: String,
+        filter: String,
+        options: SearchOptions,
+    ) -> anyhow::Result<Matches> {
+        let query_filter = QueryFilter::parse(&filter)?;
+
+        let mut found: Vec<crate::Invoice> = if options.strict {
+            self.index
+                .get(&term)
+                .map(|versions| {
+                    versions
+                        .iter()
+                        .rev()
+                        .filter(|(_, doc)| {
+                            query_filter.matches(doc.indexed.as_invoice())
+                                && (options.yanked || !doc.indexed.is_yanked())
+                        })
+                        .map(|(_, doc)| doc.indexed.as_invoice().clone())
+                        .collect()
+                })
+                .unwrap_or_default()
+        } else {
+            // A bad user-supplied pattern must not panic the server -- compile it
+            // once here and report a parse failure as an error.
+            let re = RegexBuilder::new(&term)
+                .case_insensitive(true)
+                .build()
+                .map_err(|e| anyhow::anyhow!("invalid search term '{}': {}", term, e))?;
+
+            self.index
+                .values()
+                .flat_map(|versions| versions.iter().rev())
+                .filter(|(_, doc)| {
+                    re.is_match(&doc.document)
+                        && query_filter.matches(doc.indexed.as_invoice())
+                        && (options.yanked || !doc.indexed.is_yanked())
+                })
+                .map(|(_, doc)| doc.indexed.as_invoice().clone())
+                .collect()
         };
+
+        let mut matches = Matches::new(&options);
+        matches.strict = options.strict;
+        matches.yanked = options.yanked;
+
+        Ok(paginate(matches, &mut found))
     }
 
-    false
+    /// Given an invoice, extract information from it that will be useful for searching.
+    ///
+    /// This high-level feature does not provide any guarantees about how it will
+    /// process the invoice. But it may implement Strict and/or Standard modes
+    /// described in the protocol specification.
+    ///
+    /// If the index function is given an invoice it has already indexed, it treats
+    /// the call as an update. Otherwise, it adds a new entry to the index.
+    ///
+    /// As a special note, if an invoice is yanked, the index function will mark it
+    /// as such, following the protocol specification's requirements for yanked
+    /// invoices.
+    fn index(&mut self, invoice: &crate::Invoice) -> anyhow::Result<()> {
+        let version = Version::parse(invoice.bindle.version.as_str())?;
+        let document = search_document(invoice);
+        self.index
+            .entry(invoice.bindle.name.clone())
+            .or_default()
+            .insert(
+                version,
+                IndexedDocument {
+                    indexed: Indexed::new((*invoice).clone()),
+                    document,
+                },
+            );
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -210,26 +572,46 @@ mod test {
     use crate::Invoice;
 
     #[test]
-    fn test_version_comparisons() {
-        // Do not need an exhaustive list of matches -- just a sampling to make sure
-        // the outer logic is correct.
-        let reqs = vec!["= 1.2.3", "1.2.3", "1.2.3", "^1.1", "~1.2", ""];
-
-        reqs.iter().for_each(|r| {
-            if !version_compare("1.2.3", r) {
-                panic!("Should have passed: {}", r)
-            }
-        });
+    fn query_filter_should_parse_multiple_clauses() {
+        let filter = QueryFilter::parse(
+            "version: >= 1.2.0, < 2.0.0, media_type: text/toml, author: butcher",
+        )
+        .expect("parsed filter");
+
+        assert_eq!(
+            Some(VersionReq::parse(">= 1.2.0, < 2.0.0").unwrap()),
+            filter.version
+        );
+        assert_eq!(Some("text/toml".to_owned()), filter.media_type);
+        assert_eq!(Some("butcher".to_owned()), filter.author);
+    }
+
+    #[test]
+    fn query_filter_should_report_parse_errors() {
+        assert!(QueryFilter::parse("not a clause").is_err());
+        assert!(QueryFilter::parse("version: not a version req").is_err());
+        assert!(QueryFilter::parse("bogus_field: whatever").is_err());
+    }
 
-        // Again, we do not need to test the SemVer crate -- just make sure some
-        // outliers and obvious cases are covered.
-        let reqs = vec!["2", "%^&%^&%"];
-        reqs.iter()
-            .for_each(|r| assert!(!version_compare("1.2.3", r)));
+    #[test]
+    fn query_filter_matches_should_apply_constraints_conjunctively() {
+        let inv = invoice_fixture("my/bindle".to_owned(), "1.2.3".to_owned());
 
-        // Finally, test the outliers having to do with version strings
-        let vers = vec!["", "%^&%^&%"];
-        vers.iter().for_each(|v| assert!(!version_compare(v, "^1")));
+        assert!(
+            QueryFilter::parse("version: ^1.2, author: butcher")
+                .unwrap()
+                .matches(&inv)
+        );
+        assert!(
+            !QueryFilter::parse("version: ^2, author: butcher")
+                .unwrap()
+                .matches(&inv)
+        );
+        assert!(
+            !QueryFilter::parse("media_type: application/octet-stream")
+                .unwrap()
+                .matches(&inv)
+        );
     }
 
     #[test]
@@ -243,7 +625,7 @@ mod test {
         let matches = searcher
             .query(
                 "my/bindle".to_owned(),
-                "1.2.3".to_owned(),
+                "version: 1.2.3".to_owned(),
                 SearchOptions::default(),
             )
             .expect("found some matches");
@@ -254,7 +636,7 @@ mod test {
         let matches = searcher
             .query(
                 "my/bindle2".to_owned(),
-                "1.2.3".to_owned(),
+                "version: 1.2.3".to_owned(),
                 SearchOptions::default(),
             )
             .expect("found some matches");
@@ -264,7 +646,7 @@ mod test {
         let matches = searcher
             .query(
                 "my/bindle".to_owned(),
-                "1.2.99".to_owned(),
+                "version: 1.2.99".to_owned(),
                 SearchOptions::default(),
             )
             .expect("found some matches");
@@ -273,6 +655,142 @@ mod test {
         // TODO: Need to test yanked bindles
     }
 
+    #[test]
+    fn strict_engine_should_honor_yanked_option() {
+        let mut inv = invoice_fixture("my/bindle".to_owned(), "1.2.3".to_owned());
+        inv.yanked = Some(true);
+        let mut searcher = StrictEngine::default();
+        searcher.index(&inv).expect("succesfully indexed my/bindle");
+
+        // By default, yanked invoices are not returned.
+        let matches = searcher
+            .query(
+                "my/bindle".to_owned(),
+                "".to_owned(),
+                SearchOptions::default(),
+            )
+            .expect("found some matches");
+        assert!(matches.invoices.is_empty());
+        assert!(!matches.yanked);
+
+        // When explicitly requested, yanked invoices are returned.
+        let matches = searcher
+            .query(
+                "my/bindle".to_owned(),
+                "".to_owned(),
+                SearchOptions {
+                    yanked: true,
+                    ..Default::default()
+                },
+            )
+            .expect("found some matches");
+        assert!(!matches.invoices.is_empty());
+        assert!(matches.yanked);
+    }
+
+    #[test]
+    fn strict_engine_should_support_exists_and_count() {
+        let mut searcher = StrictEngine::default();
+        for version in &["1.2.3", "1.3.0"] {
+            let inv = invoice_fixture("my/bindle".to_owned(), version.to_string());
+            searcher.index(&inv).expect("succesfully indexed my/bindle");
+        }
+
+        assert!(
+            searcher
+                .exists("my/bindle".to_owned(), "".to_owned())
+                .expect("exists succeeded")
+        );
+        assert!(
+            !searcher
+                .exists("my/bindle2".to_owned(), "".to_owned())
+                .expect("exists succeeded")
+        );
+
+        assert_eq!(
+            2,
+            searcher
+                .count("my/bindle".to_owned(), "".to_owned())
+                .expect("count succeeded")
+        );
+        assert_eq!(
+            1,
+            searcher
+                .count("my/bindle".to_owned(), "version: >=1.3".to_owned())
+                .expect("count succeeded")
+        );
+    }
+
+    #[test]
+    fn strict_engine_should_index_multiple_versions() {
+        let mut searcher = StrictEngine::default();
+        for version in &["1.2.3", "1.3.0", "2.0.0"] {
+            let inv = invoice_fixture("my/bindle".to_owned(), version.to_string());
+            searcher.index(&inv).expect("succesfully indexed my/bindle");
+        }
+        // Only one name is indexed, but it now holds every version.
+        assert_eq!(1, searcher.index.len());
+
+        let matches = searcher
+            .query(
+                "my/bindle".to_owned(),
+                "version: >=1.2, <2".to_owned(),
+                SearchOptions::default(),
+            )
+            .expect("found some matches");
+
+        let versions: Vec<String> = matches
+            .invoices
+            .iter()
+            .map(|i| i.bindle.version.clone())
+            .collect();
+        assert_eq!(vec!["1.3.0".to_owned(), "1.2.3".to_owned()], versions);
+    }
+
+    #[test]
+    fn standard_engine_should_match_substrings_and_regexes() {
+        let inv = invoice_fixture("my/bindle".to_owned(), "1.2.3".to_owned());
+        let mut searcher = StandardEngine::default();
+        searcher.index(&inv).expect("succesfully indexed my/bindle");
+
+        // Substring match against the description.
+        let matches = searcher
+            .query("bar".to_owned(), "".to_owned(), SearchOptions::default())
+            .expect("found some matches");
+        assert!(!matches.invoices.is_empty());
+
+        // Regex match against a parcel's media type.
+        let matches = searcher
+            .query(
+                r"text/\w+".to_owned(),
+                "".to_owned(),
+                SearchOptions::default(),
+            )
+            .expect("found some matches");
+        assert!(!matches.invoices.is_empty());
+
+        // No match.
+        let matches = searcher
+            .query(
+                "nonexistant".to_owned(),
+                "".to_owned(),
+                SearchOptions::default(),
+            )
+            .expect("found some matches");
+        assert!(matches.invoices.is_empty());
+
+        // A malformed regex should be reported as an error, not panic.
+        assert!(
+            searcher
+                .query(
+                    "(unterminated".to_owned(),
+                    "".to_owned(),
+                    SearchOptions::default()
+                )
+                .is_err()
+        );
+    }
+
     fn invoice_fixture(name: String, version: String) -> Invoice {
         let labels = vec![
             crate::Label {
@@ -320,4 +838,4 @@ mod test {
             group: None,
         }
     }
-}
\ No newline at end of file
+}